@@ -0,0 +1,38 @@
+//! The router's static configuration, deserialized from the router's YAML config file.
+//!
+//! This module only defines the pieces of `Configuration` that `orbiter` needs to read. The rest
+//! of the router's configuration surface lives alongside it in the full crate.
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// The router's configuration.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Configuration {
+    /// Configuration for Apollo-authored plugins, reported (by shape only) to Orbiter.
+    #[serde(default)]
+    pub(crate) apollo_plugins: ApolloPlugins,
+
+    /// Settings controlling Orbiter's anonymous usage telemetry.
+    #[serde(default)]
+    pub(crate) telemetry_reporting: TelemetryReportingConfig,
+}
+
+/// The set of Apollo-authored plugins configured for this router, keyed by plugin name.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub(crate) struct ApolloPlugins {
+    pub(crate) plugins: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Opt-in settings for Orbiter's anonymous usage telemetry.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct TelemetryReportingConfig {
+    /// When set, every `UsageReport` Orbiter sends (or fails to send) is appended as
+    /// newline-delimited JSON under this directory, rotated once it grows too large. Falls back
+    /// to the `APOLLO_ROUTER_USAGE_LOG_DIR` env var when unset, for local testing convenience.
+    #[serde(default)]
+    pub(crate) usage_report_log_dir: Option<PathBuf>,
+}
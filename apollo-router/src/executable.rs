@@ -0,0 +1,69 @@
+//! Command line interface for the router executable.
+//!
+//! This module only defines the pieces of `Opt` that `orbiter` needs: the flags it inspects for
+//! anonymous usage reporting, and the `dump-telemetry` subcommand that previews that report.
+
+use crate::orbiter;
+use crate::spec::Schema;
+use crate::Configuration;
+use clap::Parser;
+use clap::Subcommand;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tower::BoxError;
+
+/// Command line options for the router executable.
+#[derive(Parser, Debug)]
+#[clap(name = "router", about = "Apollo Router")]
+pub struct Opt {
+    /// Path to the router's configuration file.
+    #[clap(long = "config-path", env = "APOLLO_ROUTER_CONFIG_PATH")]
+    pub(crate) config_path: Option<PathBuf>,
+
+    /// The Apollo key, used to fetch the supergraph schema from Apollo Uplink.
+    #[clap(long = "apollo-key", env = "APOLLO_KEY")]
+    pub(crate) apollo_key: Option<String>,
+
+    /// The Apollo graph ref, used to fetch the supergraph schema from Apollo Uplink.
+    #[clap(long = "apollo-graph-ref", env = "APOLLO_GRAPH_REF")]
+    pub(crate) apollo_graph_ref: Option<String>,
+
+    /// When absent, the router starts normally.
+    #[clap(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+/// Subcommands supported by the router executable, in addition to just running the router.
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// Preview the anonymous `UsageReport` Orbiter would send for the current configuration and
+    /// schema, without transmitting anything.
+    DumpTelemetry {
+        /// Write the report to this file instead of stdout.
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+impl Opt {
+    /// Runs `self.command`, if one was given. Returns `Ok(true)` when a subcommand handled the
+    /// process (the caller should exit rather than starting the router), or `Ok(false)` when the
+    /// router should start normally.
+    pub(crate) fn run_command(
+        &self,
+        configuration: Arc<Configuration>,
+        schema: Arc<Schema>,
+    ) -> Result<bool, BoxError> {
+        match &self.command {
+            Some(Command::DumpTelemetry { output }) => {
+                let report = orbiter::dump_telemetry(configuration, schema)?;
+                match output {
+                    Some(path) => std::fs::write(path, report)?,
+                    None => println!("{}", report),
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
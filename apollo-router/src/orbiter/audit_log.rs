@@ -0,0 +1,210 @@
+//! Opt-in local persistence of every `UsageReport` Orbiter attempts to send, for operators in
+//! regulated environments who need a durable, inspectable record of what telemetry left the
+//! process and when. Entries are appended as newline-delimited JSON and the log file is rotated
+//! once it grows past a size threshold.
+//!
+//! Controlled by `telemetry_reporting.usage_report_log_dir` in `Configuration`. The
+//! `APOLLO_ROUTER_USAGE_LOG_DIR` env var is also accepted, for local testing without a config
+//! file; the config key takes precedence when both are set.
+
+use super::UsageReport;
+use crate::Configuration;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tower::BoxError;
+
+const LOG_DIR_ENV: &str = "APOLLO_ROUTER_USAGE_LOG_DIR";
+const LOG_FILE_NAME: &str = "usage-reports.ndjson";
+const MAX_LOG_FILE_BYTES: u64 = 10 * 1024 * 1024;
+const MAX_ROTATED_GENERATIONS: u32 = 5;
+
+/// Serializes rotation and appends. Each `create()` (config/schema reload) sends its usage
+/// report from a freshly spawned thread, so without this, two reloads racing a rotation could
+/// both see the pre-rotation file and clobber each other's renames.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// One line of the audit log: whether the send succeeded, the error if it didn't, and the full
+/// report that was (attempted to be) sent, so the log alone is enough to reconstruct history.
+#[derive(Debug, serde::Serialize)]
+struct AuditLogEntry<'a> {
+    sent: bool,
+    error: Option<String>,
+    #[serde(flatten)]
+    report: &'a UsageReport,
+}
+
+/// Appends `report`, and whether `send_result` succeeded, to the configured audit log. A no-op
+/// unless a log directory is configured, either via `telemetry_reporting.usage_report_log_dir`
+/// or the `APOLLO_ROUTER_USAGE_LOG_DIR` env var. Best-effort: any failure (missing permissions, a
+/// bad path, a full disk) is logged at debug level and never propagates, so a broken audit log
+/// can never block or fail the `create()` path.
+pub(crate) fn record(
+    report: &UsageReport,
+    send_result: &Result<String, BoxError>,
+    configuration: &Configuration,
+) {
+    let dir = match configured_log_dir(configuration) {
+        Some(dir) => dir,
+        None => return,
+    };
+
+    if let Err(e) = append(&dir, report, send_result) {
+        tracing::debug!("failed to record usage report to audit log: {}", e);
+    }
+}
+
+fn configured_log_dir(configuration: &Configuration) -> Option<PathBuf> {
+    if let Some(dir) = &configuration.telemetry_reporting.usage_report_log_dir {
+        return Some(dir.clone());
+    }
+
+    match std::env::var(LOG_DIR_ENV) {
+        Ok(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => None,
+    }
+}
+
+fn append(
+    dir: &Path,
+    report: &UsageReport,
+    send_result: &Result<String, BoxError>,
+) -> Result<(), BoxError> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(LOG_FILE_NAME);
+
+    // Poisoning only happens if a previous writer panicked mid-write; a stale lock shouldn't
+    // stop later best-effort writes, so recover the guard rather than propagating the poison.
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    rotate_if_needed(&path)?;
+
+    let entry = AuditLogEntry {
+        sent: send_result.is_ok(),
+        error: send_result.as_ref().err().map(|e| e.to_string()),
+        report,
+    };
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+/// Rotates `path` once it grows past `MAX_LOG_FILE_BYTES`, keeping up to `MAX_ROTATED_GENERATIONS`
+/// previous generations (`usage-reports.ndjson.1`, `.2`, ...) with the oldest evicted first.
+fn rotate_if_needed(path: &Path) -> Result<(), BoxError> {
+    let len = match std::fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(_) => return Ok(()),
+    };
+
+    if len < MAX_LOG_FILE_BYTES {
+        return Ok(());
+    }
+
+    let oldest = rotated_path(path, MAX_ROTATED_GENERATIONS);
+    let _ = std::fs::remove_file(&oldest);
+
+    for generation in (1..MAX_ROTATED_GENERATIONS).rev() {
+        let from = rotated_path(path, generation);
+        let to = rotated_path(path, generation + 1);
+        let _ = std::fs::rename(from, to);
+    }
+
+    std::fs::rename(path, rotated_path(path, 1))?;
+    Ok(())
+}
+
+fn rotated_path(path: &Path, generation: u32) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", generation));
+    PathBuf::from(rotated)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A fresh, empty scratch directory for one test, namespaced by test name and pid so
+    /// parallel test runs don't collide.
+    fn scratch_dir(test_name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "apollo_router_audit_log_test_{}_{}",
+            test_name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("scratch dir should be creatable");
+        dir
+    }
+
+    #[test]
+    fn test_rotated_path_appends_generation_suffix() {
+        let path = PathBuf::from("/tmp/usage-reports.ndjson");
+        assert_eq!(
+            rotated_path(&path, 1),
+            PathBuf::from("/tmp/usage-reports.ndjson.1")
+        );
+        assert_eq!(
+            rotated_path(&path, 5),
+            PathBuf::from("/tmp/usage-reports.ndjson.5")
+        );
+    }
+
+    #[test]
+    fn test_rotate_if_needed_is_a_no_op_when_file_is_missing() {
+        let dir = scratch_dir("missing");
+        let path = dir.join(LOG_FILE_NAME);
+
+        rotate_if_needed(&path).expect("a missing file is not an error");
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_rotate_if_needed_leaves_small_file_alone() {
+        let dir = scratch_dir("small");
+        let path = dir.join(LOG_FILE_NAME);
+        std::fs::write(&path, b"under the threshold").unwrap();
+
+        rotate_if_needed(&path).expect("a file under the threshold is not rotated");
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"under the threshold");
+    }
+
+    #[test]
+    fn test_rotate_if_needed_shifts_generations_and_evicts_oldest() {
+        let dir = scratch_dir("rotate");
+        let path = dir.join(LOG_FILE_NAME);
+        std::fs::write(&path, vec![0u8; MAX_LOG_FILE_BYTES as usize]).unwrap();
+        std::fs::write(rotated_path(&path, 1), b"gen1").unwrap();
+        std::fs::write(rotated_path(&path, 4), b"gen4").unwrap();
+        std::fs::write(rotated_path(&path, MAX_ROTATED_GENERATIONS), b"oldest").unwrap();
+
+        rotate_if_needed(&path).expect("an over-threshold file should rotate");
+
+        assert!(
+            !path.exists(),
+            "the over-threshold file should have been renamed away"
+        );
+        assert_eq!(
+            std::fs::metadata(rotated_path(&path, 1))
+                .unwrap()
+                .len(),
+            MAX_LOG_FILE_BYTES,
+            "generation 1 should now hold what used to be the live file"
+        );
+        assert_eq!(
+            std::fs::read(rotated_path(&path, 2)).unwrap(),
+            b"gen1",
+            "generation 1 should have shifted to generation 2"
+        );
+        assert_eq!(
+            std::fs::read(rotated_path(&path, MAX_ROTATED_GENERATIONS)).unwrap(),
+            b"gen4",
+            "generation 4 should have shifted into the oldest slot, evicting what was there"
+        );
+    }
+}
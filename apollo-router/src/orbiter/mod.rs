@@ -9,16 +9,28 @@ use http::header::USER_AGENT;
 use lazy_static::lazy_static;
 use serde::Serialize;
 use serde_json::{Map, Value};
+use std::panic;
 use std::sync::Arc;
+use std::sync::Once;
 use std::{env, thread};
+use sysinfo::SystemExt;
 use tower::BoxError;
 use uuid::Uuid;
 
+mod audit_log;
+mod otlp;
+
 lazy_static! {
     /// This session id is created once when the router starts. It persists between config reloads.
     static ref SESSION_ID: Uuid = Uuid::new_v4();
 }
 
+static PANIC_HOOK: Once = Once::new();
+
+/// The maximum length we'll keep of a sanitized panic message. Long enough to be useful, short
+/// enough that it can't accidentally carry an entire log line's worth of user data with it.
+const MAX_ERROR_REASON_LEN: usize = 128;
+
 /// Platform represents the platform the CLI is being run from
 #[derive(Debug, Serialize)]
 struct Platform {
@@ -27,6 +39,94 @@ struct Platform {
 
     /// if we think this command is being run in CI
     continuous_integration: Option<ci_info::types::Vendor>,
+
+    /// the CPU architecture, e.g. `x86_64` or `aarch64`
+    architecture: String,
+
+    /// the container runtime we think the router is running under, if any. Bare-metal and VM
+    /// hosts report `None` here.
+    container_runtime: Option<ContainerRuntime>,
+
+    /// the number of logical CPU cores available to the router. Best effort and not guaranteed
+    /// to be populated on all platforms.
+    cpu_cores: Option<usize>,
+
+    /// total system memory, rounded down to the nearest power-of-two number of GB so this stays
+    /// a coarse bucket rather than an identifying value.
+    memory_gb_bucket: Option<u64>,
+}
+
+impl Platform {
+    /// Builds a `Platform` describing the machine the router is currently running on.
+    fn current() -> Platform {
+        Platform {
+            os: get_os(),
+            continuous_integration: ci_info::get().vendor,
+            architecture: std::env::consts::ARCH.to_string(),
+            container_runtime: detect_container_runtime(),
+            cpu_cores: std::thread::available_parallelism().map(|n| n.get()).ok(),
+            memory_gb_bucket: get_memory_gb_bucket(),
+        }
+    }
+}
+
+/// The container runtime the router appears to be running under, detected with multiple
+/// fallback signals so a single missing file never hides the others.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum ContainerRuntime {
+    Docker,
+    Kubernetes,
+}
+
+/// Detects whether the router is running under Docker or Kubernetes. This never panics, even if
+/// none of the signal files exist or are readable (e.g. non-Linux platforms): each check is
+/// independently best effort, and the absence of all of them just means "not containerized".
+fn detect_container_runtime() -> Option<ContainerRuntime> {
+    if env::var("KUBERNETES_SERVICE_HOST").is_ok() {
+        return Some(ContainerRuntime::Kubernetes);
+    }
+
+    if std::path::Path::new("/.dockerenv").exists() {
+        return Some(ContainerRuntime::Docker);
+    }
+
+    std::fs::read_to_string("/proc/1/cgroup")
+        .ok()
+        .and_then(|cgroup| runtime_from_cgroup(&cgroup))
+}
+
+/// Inspects the contents of `/proc/1/cgroup` for container-runtime signals. Kubernetes is checked
+/// before Docker/containerd since a Kubernetes pod's cgroup commonly also contains a `docker` or
+/// `containerd` substring from the underlying runtime, and Kubernetes is the more specific signal.
+fn runtime_from_cgroup(cgroup: &str) -> Option<ContainerRuntime> {
+    if cgroup.contains("kubepods") {
+        Some(ContainerRuntime::Kubernetes)
+    } else if cgroup.contains("docker") || cgroup.contains("containerd") {
+        Some(ContainerRuntime::Docker)
+    } else {
+        None
+    }
+}
+
+/// Total system memory, bucketed to the nearest power-of-two number of GB (e.g. a 10 GB host
+/// reports `8`). Returns `None` rather than panicking if memory can't be determined.
+fn get_memory_gb_bucket() -> Option<u64> {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let total_kb = system.total_memory();
+    if total_kb == 0 {
+        return None;
+    }
+
+    Some(bucket_memory_gb(total_kb as f64 / 1024.0 / 1024.0))
+}
+
+/// Buckets `total_gb` down to the nearest power-of-two number of GB (e.g. `12.0` -> `8`, `16.0`
+/// -> `16`), so the reported value is always a coarse bucket rather than an exact, more
+/// identifying figure.
+fn bucket_memory_gb(total_gb: f64) -> u64 {
+    total_gb.log2().floor().exp2() as u64
 }
 
 /// Platform represents the platform the CLI is being run from
@@ -44,8 +144,132 @@ struct UsageReport {
     usage: Map<String, serde_json::Value>,
 }
 
+/// A one-off, best-effort event sent when the router panics or otherwise exits abnormally.
+/// It carries no user data, just enough to tell maintainers which failure modes actually happen
+/// in the wild. `error_reason` is deliberately flat (rather than nested JSON) so it's trivially
+/// aggregatable by downstream tooling.
+#[derive(Debug, Serialize)]
+struct CrashReport {
+    /// A random ID that is generated on first startup of the Router. It is persistent between restarts. This is best effort and not guaranteed to be populated
+    machine_id: Uuid,
+    /// A random ID that is generated on first startup of the Router. It is not persistent between restarts of the Router, but will be persistent for hot reloads
+    session_id: Uuid,
+    /// The version of the Router
+    version: String,
+    /// Information about the current architecture/platform
+    platform: Platform,
+    /// A coarse, non-sensitive description of what went wrong, e.g. `config_parse` or a
+    /// truncated, sanitized panic message. Never contains paths, values, or other user data.
+    error_reason: String,
+}
+
+/// Installs a panic hook that reports an anonymous, best-effort crash event to Orbiter before
+/// chaining to the previously installed hook. This is only installed once, even across config
+/// reloads, since `std::panic::set_hook` is process-global.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |panic_info| {
+            if env::var("APOLLO_TELEMETRY_DISABLED").unwrap_or_default() != "true" {
+                let error_reason = sanitize_panic_message(panic_info);
+                // Spawn the send rather than blocking the unwinding/exiting thread on it, same as
+                // the regular usage report. Catch our own panics too, so a bug in telemetry
+                // reporting can never prevent the real panic from being reported or delay exit.
+                let _ = thread::Builder::new().spawn(move || {
+                    let _ = panic::catch_unwind(|| send_anonymous_crash_to_orbiter(error_reason));
+                });
+            }
+            previous_hook(panic_info);
+        }));
+    });
+}
+
+/// Reduces a panic message down to a coarse, non-identifying reason. This strips the payload of
+/// anything that looks like a file path or a quoted value and truncates what's left, so we learn
+/// that (for example) a config parse failed without learning anything about the user's config.
+fn sanitize_panic_message(panic_info: &panic::PanicInfo) -> String {
+    let message = panic_info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic_info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+
+    sanitize_error_reason(message.lines().next().unwrap_or_default())
+}
+
+/// Reduces a raw error/panic message down to a coarse, non-identifying reason: strips quoted
+/// spans and anything that looks like a file path, then truncates what's left.
+fn sanitize_error_reason(message: &str) -> String {
+    let sanitized: String = strip_quoted_spans(message)
+        .split_whitespace()
+        .filter(|word| !word.contains('/') && !word.contains('\\'))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    sanitized.chars().take(MAX_ERROR_REASON_LEN).collect()
+}
+
+/// Removes quoted spans (`'...'`/`"..."`) from `message`, including ones that contain whitespace,
+/// e.g. `invalid value 'sk-12345 secret' for key 'API_KEY'` drops both quoted values in full. A
+/// quote character only opens a span when it sits at a word boundary (start of string, or not
+/// preceded by an alphanumeric character), so an apostrophe inside a contraction like "can't" is
+/// left alone rather than mistaken for a quote delimiter. An unmatched opening quote drops
+/// everything after it, failing safe rather than leaking the remainder of the message.
+fn strip_quoted_spans(message: &str) -> String {
+    let chars: Vec<char> = message.chars().collect();
+    let mut result = String::with_capacity(message.len());
+    let mut open_quote: Option<char> = None;
+
+    for (i, &c) in chars.iter().enumerate() {
+        match open_quote {
+            Some(q) if c == q => open_quote = None,
+            Some(_) => {}
+            None if (c == '\'' || c == '"') && is_word_boundary(&chars, i) => {
+                open_quote = Some(c);
+            }
+            None => result.push(c),
+        }
+    }
+
+    result
+}
+
+fn is_word_boundary(chars: &[char], i: usize) -> bool {
+    i == 0 || !chars[i - 1].is_alphanumeric()
+}
+
+/// Reports a non-panic abnormal shutdown the same way a panic is reported: same report shape,
+/// same best-effort non-blocking send, same `APOLLO_TELEMETRY_DISABLED` opt-out. Used when
+/// `create()` fails (e.g. bad config or a schema load failure), which on first boot makes the
+/// router exit and on a reload leaves it on its previous config, in both cases without
+/// unwinding, so those failure modes aren't invisible to `install_panic_hook`'s panic-only
+/// coverage.
+fn report_abnormal_shutdown(error_reason: &str) {
+    if env::var("APOLLO_TELEMETRY_DISABLED").unwrap_or_default() != "true" {
+        let error_reason = sanitize_error_reason(error_reason);
+        let _ = thread::Builder::new().spawn(move || {
+            let _ = panic::catch_unwind(|| send_anonymous_crash_to_orbiter(error_reason));
+        });
+    }
+}
+
+fn send_anonymous_crash_to_orbiter(error_reason: String) {
+    let body = CrashReport {
+        machine_id: get_machine_id(),
+        session_id: *SESSION_ID,
+        version: std::env!("CARGO_PKG_VERSION").to_string(),
+        platform: Platform::current(),
+        error_reason,
+    };
+
+    if let Err(e) = send(&body) {
+        tracing::debug!("failed to send anonymous crash report: {}", e);
+    }
+}
+
 /// A service factory that will report some anonymous telemetry to Apollo. It can be disabled by users, but the data is useful for helping us to decide where to spend our efforts.
-/// In future we should try and move this towards otel metrics, this will allow us to send the information direct to something that ingests OTLP.
+/// Usage can additionally be exported as OTLP metrics, see `otlp::maybe_send_usage_metrics_via_otlp`, so self-hosted users can point this data at their own collector.
 /// The data sent looks something like this:
 /// ```json
 /// {
@@ -54,7 +278,11 @@ struct UsageReport {
 ///   "version": "1.4.0",
 ///   "platform": {
 ///     "os": "linux",
-///     "continuous_integration": null
+///     "continuous_integration": null,
+///     "architecture": "x86_64",
+///     "container_runtime": "docker",
+///     "cpu_cores": 8,
+///     "memory_gb_bucket": 16
 ///   },
 ///   "usage": {
 ///     "configuration.headers.all.request.propagate.named": "set",
@@ -101,6 +329,7 @@ impl<T: SupergraphServiceConfigurator> SupergraphServiceConfigurator
             .await
             .map(|factory| {
                 if env::var("APOLLO_TELEMETRY_DISABLED").unwrap_or_default() != "true" {
+                    install_panic_hook();
                     thread::spawn(|| {
                         tracing::debug!("sending anonymous usage data to Apollo");
                         send_anonymous_metrics_to_orbiter(configuration, schema);
@@ -108,12 +337,28 @@ impl<T: SupergraphServiceConfigurator> SupergraphServiceConfigurator
                 }
                 factory
             })
+            .map_err(|err| {
+                // A failed `create()` means the router is about to exit (first boot) or stay on
+                // its previous config (a reload) without ever unwinding, so it's invisible to the
+                // panic hook above. Report it the same best-effort way.
+                report_abnormal_shutdown(&err.to_string());
+                err
+            })
     }
 }
 
-fn send_anonymous_metrics_to_orbiter(configuration: Arc<Configuration>, _schema: Arc<Schema>) {
+/// Builds the `UsageReport` that would be sent to Orbiter for the given configuration, schema,
+/// and command line arguments, without sending anything. It is kept separate from sending so that both
+/// the real sender and `router dump-telemetry` (see `executable::Opt`) can share the exact same
+/// assembly logic: what operators can preview locally is guaranteed to be what actually gets
+/// transmitted. `matches` is taken explicitly (rather than parsed internally from
+/// `std::env::args()`) so this can be exercised directly in tests.
+fn build_usage_report(
+    configuration: &Configuration,
+    _schema: &Schema,
+    matches: &clap::ArgMatches,
+) -> UsageReport {
     let machine_id = get_machine_id();
-    let os = get_os();
     let mut usage = serde_json::Map::new();
     // We only report apollo plugins. This way we don't risk leaking sensitive data if the user has customized the router and added their own plugins.
     // In addition, we only report the shape of the configuration
@@ -122,7 +367,6 @@ fn send_anonymous_metrics_to_orbiter(configuration: Arc<Configuration>, _schema:
     }
 
     // Check the command line options. This encapsulates both env and command line functionality
-    let matches = Opt::command().get_matches();
     Opt::command().get_arguments().for_each(|a| {
         // This logic took a lot of trial and error to figure out.
         // If there are no defaults then the setting of the arg itself is enough for us to record it.
@@ -141,26 +385,51 @@ fn send_anonymous_metrics_to_orbiter(configuration: Arc<Configuration>, _schema:
         }
     });
 
-    let body = UsageReport {
+    UsageReport {
         machine_id,
         session_id: *SESSION_ID,
         version: std::env!("CARGO_PKG_VERSION").to_string(),
-        platform: Platform {
-            os,
-            continuous_integration: ci_info::get().vendor,
-        },
+        platform: Platform::current(),
         usage,
-    };
+    }
+}
+
+fn send_anonymous_metrics_to_orbiter(configuration: Arc<Configuration>, schema: Arc<Schema>) {
+    let matches = Opt::command().get_matches();
+    let body = build_usage_report(&configuration, &schema, &matches);
 
-    if let Err(e) = send(body) {
+    otlp::maybe_send_usage_metrics_via_otlp(&body);
+
+    let result = send(&body);
+    audit_log::record(&body, &result, &configuration);
+    if let Err(e) = result {
         tracing::debug!("failed to send anonymous usage: {}", e);
     }
 }
 
-fn send(body: UsageReport) -> Result<String, BoxError> {
-    tracing::debug!("anonymous usage: {}", serde_json::to_string_pretty(&body)?);
+/// Assembles the same report `send_anonymous_metrics_to_orbiter` would transmit and pretty-prints
+/// it as JSON, without sending anything over the network. Backs the `router dump-telemetry`
+/// subcommand (see `executable::Opt::run_command`) so privacy-conscious operators can audit
+/// exactly what Orbiter would see for their configuration.
+pub(crate) fn dump_telemetry(
+    configuration: Arc<Configuration>,
+    schema: Arc<Schema>,
+) -> Result<String, BoxError> {
+    let matches = Opt::command().get_matches();
+    let report = build_usage_report(&configuration, &schema, &matches);
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+/// How long we'll wait on the telemetry endpoint before giving up. Short, since this is
+/// best-effort reporting that must never meaningfully delay a reload or process exit.
+const SEND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
 
-    Ok(reqwest::blocking::Client::new()
+fn send<T: Serialize>(body: &T) -> Result<String, BoxError> {
+    tracing::debug!("anonymous usage: {}", serde_json::to_string_pretty(body)?);
+
+    Ok(reqwest::blocking::Client::builder()
+        .timeout(SEND_TIMEOUT)
+        .build()?
         .post("http://localhost:8888/telemetry")
         .header(USER_AGENT, "router")
         .json(&serde_json::to_value(body)?)
@@ -263,4 +532,125 @@ mod test {
             assert_yaml_snapshot!(usage);
         });
     }
+
+    #[test]
+    fn test_build_usage_report_snapshot() {
+        // Exercises the actual function `build_usage_report` (the shared core that both
+        // `dump_telemetry` and `send_anonymous_metrics_to_orbiter` call) against a real
+        // `Configuration`, rather than a hand-rolled `UsageReport`. `machine_id`/`session_id`/
+        // `platform` vary by host and run, so all three are redacted.
+        use crate::executable::Opt;
+        use crate::orbiter::build_usage_report;
+        use crate::spec::Schema;
+        use crate::Configuration;
+        use clap::CommandFactory;
+
+        let configuration: Configuration = serde_json::from_value(json!({
+            "apollo_plugins": {
+                "plugins": {
+                    "headers": {
+                        "all": {
+                            "request": {
+                                "propagate": { "named": "x-custom-header" },
+                                "insert": { "name": "x-my-header", "value": "my-value" }
+                            }
+                        }
+                    }
+                }
+            }
+        }))
+        .expect("sample configuration should deserialize");
+
+        let schema = Schema::default();
+        let matches = Opt::command().get_matches_from(["router"]);
+
+        let report = build_usage_report(&configuration, &schema, &matches);
+
+        insta::with_settings!({sort_maps => true, redactions => vec![
+            (".machine_id", "[machine_id]"),
+            (".session_id", "[session_id]"),
+            (".platform", "[platform]"),
+        ]}, {
+            assert_yaml_snapshot!(report);
+        });
+    }
+
+    #[test]
+    fn test_sanitize_error_reason_strips_multi_word_quoted_values() {
+        use crate::orbiter::sanitize_error_reason;
+
+        let message = "invalid value 'sk-12345 secret' for key 'API_KEY'";
+        assert_eq!(sanitize_error_reason(message), "invalid value for key");
+    }
+
+    #[test]
+    fn test_sanitize_error_reason_keeps_contractions() {
+        use crate::orbiter::sanitize_error_reason;
+
+        assert_eq!(
+            sanitize_error_reason("can't parse config, doesn't exist"),
+            "can't parse config, doesn't exist"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_error_reason_strips_paths() {
+        use crate::orbiter::sanitize_error_reason;
+
+        assert_eq!(
+            sanitize_error_reason("failed to read /etc/router/config.yaml"),
+            "failed to read"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_error_reason_fails_safe_on_unmatched_quote() {
+        use crate::orbiter::sanitize_error_reason;
+
+        assert_eq!(
+            sanitize_error_reason("unexpected token 'unterminated"),
+            "unexpected token"
+        );
+    }
+
+    #[test]
+    fn test_runtime_from_cgroup_prefers_kubernetes_over_docker() {
+        use crate::orbiter::runtime_from_cgroup;
+        use crate::orbiter::ContainerRuntime;
+
+        // A Kubernetes pod's cgroup commonly mentions containerd/docker too; kubepods must win.
+        let cgroup = "0::/kubepods/besteffort/pod123/containerd-abc.scope";
+        assert_eq!(
+            runtime_from_cgroup(cgroup),
+            Some(ContainerRuntime::Kubernetes)
+        );
+    }
+
+    #[test]
+    fn test_runtime_from_cgroup_detects_docker() {
+        use crate::orbiter::runtime_from_cgroup;
+        use crate::orbiter::ContainerRuntime;
+
+        assert_eq!(
+            runtime_from_cgroup("0::/docker/abc123"),
+            Some(ContainerRuntime::Docker)
+        );
+    }
+
+    #[test]
+    fn test_runtime_from_cgroup_none_when_no_signal_present() {
+        use crate::orbiter::runtime_from_cgroup;
+
+        assert_eq!(runtime_from_cgroup("0::/"), None);
+    }
+
+    #[test]
+    fn test_bucket_memory_gb_rounds_down_to_power_of_two() {
+        use crate::orbiter::bucket_memory_gb;
+
+        assert_eq!(bucket_memory_gb(16.0), 16);
+        assert_eq!(bucket_memory_gb(12.0), 8);
+        assert_eq!(bucket_memory_gb(31.9), 16);
+        assert_eq!(bucket_memory_gb(32.0), 32);
+    }
 }
@@ -0,0 +1,95 @@
+//! Opt-in OTLP metrics export for Orbiter usage reports.
+//!
+//! This reuses the same `usage` map produced by `visit_config` and, alongside the bespoke
+//! blocking HTTP POST to the telemetry endpoint, emits each config path as a metric through an
+//! OpenTelemetry `MeterProvider` backed by an OTLP exporter, so self-hosted users can point usage
+//! data at their own collector. The HTTP POST is still always made; the OTLP export runs first,
+//! on the same spawned thread.
+
+use super::UsageReport;
+use opentelemetry::metrics::MeterProvider as _;
+use opentelemetry::sdk::metrics::MeterProvider;
+use opentelemetry::sdk::Resource;
+use opentelemetry::KeyValue;
+use tower::BoxError;
+
+/// Opts in to OTLP metrics export and gives the collector endpoint to send to. When unset, usage
+/// metrics are not exported via OTLP and the legacy HTTP POST remains the only path.
+const OTLP_ENDPOINT_ENV: &str = "APOLLO_ROUTER_USAGE_OTLP_ENDPOINT";
+
+/// The leaf kind a configuration value was reported as, mirroring `visit_config`'s cases. Array
+/// lengths are stored as numbers under a `.len` path, so the path tells them apart from numbers.
+fn leaf_kind(path: &str, value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Bool(_) => "bool",
+        serde_json::Value::Number(_) if path.ends_with(".len") => "len",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "set",
+        _ => "len",
+    }
+}
+
+/// If `APOLLO_ROUTER_USAGE_OTLP_ENDPOINT` is set, exports `report.usage` as OTLP metrics to that
+/// endpoint. Best-effort: failures are logged and never propagate, mirroring the rest of
+/// Orbiter's fire-and-forget send path.
+pub(crate) fn maybe_send_usage_metrics_via_otlp(report: &UsageReport) {
+    let endpoint = match std::env::var(OTLP_ENDPOINT_ENV) {
+        Ok(endpoint) if !endpoint.is_empty() => endpoint,
+        _ => return,
+    };
+
+    if let Err(e) = send_usage_metrics_via_otlp(report, &endpoint) {
+        tracing::debug!("failed to export anonymous usage metrics via OTLP: {}", e);
+    }
+}
+
+fn send_usage_metrics_via_otlp(report: &UsageReport, endpoint: &str) -> Result<(), BoxError> {
+    // machine_id/session_id/version/os describe the process emitting the metrics, so they
+    // belong on the resource rather than as attributes repeated on every individual metric.
+    let resource = Resource::new(vec![
+        KeyValue::new("machine_id", report.machine_id.to_string()),
+        KeyValue::new("session_id", report.session_id.to_string()),
+        KeyValue::new("version", report.version.clone()),
+        KeyValue::new("os", report.platform.os.clone()),
+    ]);
+
+    // `opentelemetry_otlp`'s periodic reader spawns its export task via `tokio::spawn` as soon
+    // as the pipeline is built, and this function is reached from a bare `std::thread::spawn`
+    // closure (see `send_anonymous_metrics_to_orbiter`) with no ambient Tokio runtime. Build,
+    // record, and shut the pipeline down inside a dedicated current-thread runtime so there's
+    // somewhere for that spawned task to actually run.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async {
+        let provider: MeterProvider = opentelemetry_otlp::new_pipeline()
+            .metrics(opentelemetry::runtime::Tokio)
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(endpoint),
+            )
+            .with_resource(resource)
+            .build()?;
+
+        let meter = provider.meter("apollo-router-orbiter");
+        for (path, value) in &report.usage {
+            // Each config path becomes its own metric, named after the path, so the shape of
+            // the configuration falls directly out of which metrics exist, not just their values.
+            let counter = meter.u64_counter(path.clone()).init();
+            let amount = match value {
+                serde_json::Value::Number(n) => n.as_u64().unwrap_or(1),
+                _ => 1,
+            };
+            counter.add(amount, &[KeyValue::new("kind", leaf_kind(path, value))]);
+        }
+
+        // This is a one-shot export, not a long-lived metrics pipeline, so flush and shut the
+        // provider down here rather than leaving its batch processor running for the router's
+        // lifetime.
+        provider.shutdown()
+    })?;
+
+    Ok(())
+}
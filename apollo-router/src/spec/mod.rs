@@ -0,0 +1,8 @@
+//! The router's parsed supergraph schema.
+//!
+//! This module only defines the minimal `Schema` surface that `orbiter` needs: a handle it
+//! threads through but doesn't currently inspect.
+
+/// The router's parsed supergraph schema.
+#[derive(Debug, Default)]
+pub struct Schema;